@@ -3,47 +3,94 @@
 /// This module implements a heuristic-based approach to detect AI-generated text.
 /// It analyzes various patterns common in AI-generated content vs human writing.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// One scoring factor's contribution to the overall AI percentage.
+pub struct FactorScore {
+    pub name: &'static str,
+    pub score: f32,
+    pub weight: f32,
+}
+
+/// A phrase from the AI-phrase list found in the analyzed text, along with
+/// its character offsets so a frontend can highlight it.
+pub struct PhraseMatch {
+    pub phrase: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The full, explainable result of analyzing a piece of text: the overall
+/// score plus the per-factor breakdown and phrase matches that produced it.
+pub struct AnalysisBreakdown {
+    pub ai_percentage: f32,
+    pub factors: Vec<FactorScore>,
+    pub matched_phrases: Vec<PhraseMatch>,
+}
 
 pub struct TextAnalyzer;
 
 impl TextAnalyzer {
     /// Analyze text and return AI probability score (0-100)
     pub fn analyze(text: &str) -> f32 {
+        Self::analyze_detailed(text).ai_percentage
+    }
+
+    /// Analyze text and return the full per-factor breakdown, not just the
+    /// final score, so callers can explain why a verdict was reached.
+    pub fn analyze_detailed(text: &str) -> AnalysisBreakdown {
         if text.trim().is_empty() {
-            return 50.0;
+            return AnalysisBreakdown {
+                ai_percentage: 50.0,
+                factors: Vec::new(),
+                matched_phrases: Vec::new(),
+            };
         }
 
-        let mut ai_score = 0.0;
-        let mut total_weight = 0.0;
-
-        // Factor 1: Sentence length uniformity (weight: 25%)
-        let uniformity_score = Self::analyze_sentence_uniformity(text);
-        ai_score += uniformity_score * 0.25;
-        total_weight += 0.25;
-
-        // Factor 2: Vocabulary diversity (weight: 20%)
-        let diversity_score = Self::analyze_vocabulary_diversity(text);
-        ai_score += diversity_score * 0.20;
-        total_weight += 0.20;
-
-        // Factor 3: AI-common phrases (weight: 30%)
-        let phrase_score = Self::detect_ai_phrases(text);
-        ai_score += phrase_score * 0.30;
-        total_weight += 0.30;
-
-        // Factor 4: Punctuation patterns (weight: 15%)
-        let punctuation_score = Self::analyze_punctuation(text);
-        ai_score += punctuation_score * 0.15;
-        total_weight += 0.15;
+        let matched_phrases = Self::find_ai_phrases(text);
+
+        let factors = vec![
+            FactorScore {
+                name: "sentence_uniformity",
+                score: Self::analyze_sentence_uniformity(text),
+                weight: 0.25,
+            },
+            FactorScore {
+                name: "vocabulary_diversity",
+                score: Self::analyze_vocabulary_diversity(text),
+                weight: 0.20,
+            },
+            FactorScore {
+                name: "ai_phrases",
+                score: Self::score_ai_phrases(&matched_phrases),
+                weight: 0.30,
+            },
+            FactorScore {
+                name: "punctuation",
+                score: Self::analyze_punctuation(text),
+                weight: 0.15,
+            },
+            FactorScore {
+                name: "structure",
+                score: Self::analyze_structure(text),
+                weight: 0.10,
+            },
+            FactorScore {
+                name: "perplexity",
+                score: Self::analyze_perplexity(text),
+                weight: 0.15,
+            },
+        ];
 
-        // Factor 5: Text length and structure (weight: 10%)
-        let structure_score = Self::analyze_structure(text);
-        ai_score += structure_score * 0.10;
-        total_weight += 0.10;
+        let ai_score: f32 = factors.iter().map(|f| f.score * f.weight).sum();
+        let total_weight: f32 = factors.iter().map(|f| f.weight).sum();
 
-        // Normalize to 0-100 range
-        (ai_score / total_weight).clamp(0.0, 100.0)
+        AnalysisBreakdown {
+            ai_percentage: (ai_score / total_weight).clamp(0.0, 100.0),
+            factors,
+            matched_phrases,
+        }
     }
 
     /// Analyze sentence length uniformity
@@ -103,45 +150,87 @@ impl TextAnalyzer {
         }
     }
 
-    /// Detect common AI phrases
-    fn detect_ai_phrases(text: &str) -> f32 {
-        let text_lower = text.to_lowercase();
-
-        let ai_phrases = [
-            "as an ai",
-            "i don't have personal",
-            "i cannot",
-            "i'm sorry, but",
-            "it's important to note",
-            "it is worth noting",
-            "furthermore",
-            "in conclusion",
-            "to summarize",
-            "delve into",
-            "multifaceted",
-            "paradigm shift",
-            "cutting-edge",
-            "state-of-the-art",
-            "best practices",
-            "leverage",
-            "utilize",
-            "facilitate",
-            "comprehensive understanding",
-        ];
+    /// The list of phrases common in AI-generated text that we scan for.
+    const AI_PHRASES: &'static [&'static str] = &[
+        "as an ai",
+        "i don't have personal",
+        "i cannot",
+        "i'm sorry, but",
+        "it's important to note",
+        "it is worth noting",
+        "furthermore",
+        "in conclusion",
+        "to summarize",
+        "delve into",
+        "multifaceted",
+        "paradigm shift",
+        "cutting-edge",
+        "state-of-the-art",
+        "best practices",
+        "leverage",
+        "utilize",
+        "facilitate",
+        "comprehensive understanding",
+    ];
+
+    /// Find every AI phrase actually present in `text`, with the character
+    /// offsets of each match (into `text` itself, not a lowercased copy) so
+    /// a frontend can highlight them directly. Matching is ASCII
+    /// case-insensitive; all entries in `AI_PHRASES` are plain ASCII, so
+    /// this never needs to compare non-ASCII case folding, and scanning by
+    /// byte is safe (no continuation byte of a multi-byte char can match an
+    /// ASCII needle byte) even though offsets are reported in characters.
+    fn find_ai_phrases(text: &str) -> Vec<PhraseMatch> {
+        let bytes = text.as_bytes();
+        let mut matches = Vec::new();
+
+        for phrase in Self::AI_PHRASES.iter() {
+            let phrase_bytes = phrase.as_bytes();
+            if phrase_bytes.is_empty() || phrase_bytes.len() > bytes.len() {
+                continue;
+            }
 
-        let mut matches = 0;
-        for phrase in ai_phrases.iter() {
-            if text_lower.contains(phrase) {
-                matches += 1;
+            let mut start = 0;
+            while start + phrase_bytes.len() <= bytes.len() {
+                if bytes[start..start + phrase_bytes.len()].eq_ignore_ascii_case(phrase_bytes) {
+                    let end = start + phrase_bytes.len();
+                    matches.push(PhraseMatch {
+                        phrase: phrase.to_string(),
+                        start: Self::byte_to_char_offset(text, start),
+                        end: Self::byte_to_char_offset(text, end),
+                    });
+                    start = end;
+                } else {
+                    start += 1;
+                }
             }
         }
 
-        // More AI phrases = higher AI probability
-        if matches >= 3 {
+        matches
+    }
+
+    /// Converts a byte offset into `s` (which must land on a char boundary,
+    /// as every offset `find_ai_phrases` passes in does) to the character
+    /// index at that position.
+    fn byte_to_char_offset(s: &str, byte_offset: usize) -> usize {
+        s[..byte_offset].chars().count()
+    }
+
+    /// Score the AI-phrase factor from how many *distinct* phrases were
+    /// found (a phrase repeated several times still counts once, matching
+    /// how this factor has always been scored).
+    fn score_ai_phrases(matches: &[PhraseMatch]) -> f32 {
+        let distinct_count = matches
+            .iter()
+            .map(|m| m.phrase.as_str())
+            .collect::<HashSet<_>>()
+            .len();
+
+        if distinct_count >= 3 {
             85.0
-        } else if matches == 2 {
+        } else if distinct_count == 2 {
             70.0
-        } else if matches == 1 {
+        } else if distinct_count == 1 {
             55.0
         } else {
             30.0
@@ -192,6 +281,68 @@ impl TextAnalyzer {
         }
     }
 
+    /// Analyze perplexity and burstiness using a small n-gram language model
+    /// AI text tends to read as uniformly "predictable" (low perplexity, low
+    /// burstiness), while human text spikes between common and surprising
+    /// word choices.
+    fn analyze_perplexity(text: &str) -> f32 {
+        let words: Vec<String> = text
+            .split_whitespace()
+            .map(|w| w.to_lowercase().trim_matches(|c: char| !c.is_alphanumeric()).to_string())
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        if words.len() < 5 {
+            return 50.0;
+        }
+
+        let model = frequency_model();
+        let mut log_probs: Vec<f64> = Vec::with_capacity(words.len());
+
+        for (i, word) in words.iter().enumerate() {
+            let log_prob = if i > 0 {
+                model
+                    .bigram_logprob(&words[i - 1], word)
+                    .unwrap_or_else(|| model.unigram_logprob(word))
+            } else {
+                model.unigram_logprob(word)
+            };
+            log_probs.push(log_prob);
+        }
+
+        let mean_neg_log_prob = -log_probs.iter().sum::<f64>() / log_probs.len() as f64;
+
+        let mean_log_prob = log_probs.iter().sum::<f64>() / log_probs.len() as f64;
+        let variance = log_probs
+            .iter()
+            .map(|&lp| {
+                let diff = lp - mean_log_prob;
+                diff * diff
+            })
+            .sum::<f64>()
+            / log_probs.len() as f64;
+
+        // Low mean perplexity + low variance (burstiness) = more AI-like.
+        // High variance (bursty, mixing common and surprising words) = more human-like.
+        let perplexity_score: f32 = if mean_neg_log_prob < 6.0 {
+            70.0
+        } else if mean_neg_log_prob < 9.0 {
+            50.0
+        } else {
+            30.0
+        };
+
+        let burstiness_score: f32 = if variance < 2.0 {
+            70.0
+        } else if variance < 6.0 {
+            45.0
+        } else {
+            20.0
+        };
+
+        ((perplexity_score + burstiness_score) / 2.0).clamp(0.0, 100.0)
+    }
+
     /// Determine verdict based on AI score
     pub fn get_verdict(ai_percentage: f32) -> String {
         if ai_percentage >= 60.0 {
@@ -204,6 +355,96 @@ impl TextAnalyzer {
     }
 }
 
+/// Floor log-probability assigned to words the model has never seen.
+const UNKNOWN_WORD_LOG_PROB: f64 = -11.0;
+
+/// A compact unigram/bigram frequency model used to estimate how
+/// "predictable" a piece of text is (perplexity and burstiness).
+///
+/// The tables are a small, hand-curated sample of common English words
+/// and word pairs with log-probabilities, bundled directly into the
+/// binary rather than loaded from an external data file.
+struct FrequencyModel {
+    unigrams: HashMap<String, f64>,
+    bigrams: HashMap<(String, String), f64>,
+}
+
+impl FrequencyModel {
+    fn new() -> Self {
+        const UNIGRAM_LOG_PROBS: &[(&str, f64)] = &[
+            ("the", -2.1),
+            ("a", -2.8),
+            ("an", -4.2),
+            ("is", -3.1),
+            ("it", -3.4),
+            ("of", -2.6),
+            ("to", -2.5),
+            ("and", -2.7),
+            ("in", -3.0),
+            ("that", -3.6),
+            ("this", -3.9),
+            ("for", -3.5),
+            ("with", -3.8),
+            ("as", -3.7),
+            ("on", -4.0),
+            ("are", -3.9),
+            ("be", -3.9),
+            ("can", -4.3),
+            ("was", -4.0),
+            ("i", -3.3),
+            ("you", -3.5),
+            ("we", -4.1),
+            ("furthermore", -7.8),
+            ("comprehensive", -8.1),
+            ("utilize", -8.4),
+            ("leverage", -8.2),
+            ("delve", -8.9),
+            ("multifaceted", -9.3),
+        ];
+
+        const BIGRAM_LOG_PROBS: &[(&str, &str, f64)] = &[
+            ("it", "is", -1.8),
+            ("is", "important", -4.5),
+            ("important", "to", -1.2),
+            ("to", "note", -3.8),
+            ("in", "conclusion", -2.9),
+            ("as", "an", -2.4),
+            ("an", "ai", -5.1),
+            ("comprehensive", "understanding", -1.5),
+            ("cutting", "edge", -1.1),
+            ("state", "of", -1.0),
+        ];
+
+        let unigrams = UNIGRAM_LOG_PROBS
+            .iter()
+            .map(|(w, p)| (w.to_string(), *p))
+            .collect();
+
+        let bigrams = BIGRAM_LOG_PROBS
+            .iter()
+            .map(|(a, b, p)| ((a.to_string(), b.to_string()), *p))
+            .collect();
+
+        FrequencyModel { unigrams, bigrams }
+    }
+
+    fn unigram_logprob(&self, word: &str) -> f64 {
+        *self.unigrams.get(word).unwrap_or(&UNKNOWN_WORD_LOG_PROB)
+    }
+
+    fn bigram_logprob(&self, prev: &str, word: &str) -> Option<f64> {
+        self.bigrams
+            .get(&(prev.to_string(), word.to_string()))
+            .copied()
+    }
+}
+
+/// Lazily builds and caches the frequency model used by `analyze_perplexity`.
+fn frequency_model() -> &'static FrequencyModel {
+    static MODEL: OnceLock<FrequencyModel> = OnceLock::new();
+    MODEL.get_or_init(FrequencyModel::new)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,4 +461,50 @@ mod tests {
         let score = TextAnalyzer::analyze(text);
         assert!(score > 50.0);
     }
+
+    #[test]
+    fn test_analyze_perplexity_short_text_is_neutral() {
+        let score = TextAnalyzer::analyze_perplexity("too short");
+        assert_eq!(score, 50.0);
+    }
+
+    #[test]
+    fn test_find_ai_phrases_returns_offsets_into_original_text() {
+        let text = "It Is Worth Noting that Furthermore this is long.";
+        let matches = TextAnalyzer::find_ai_phrases(text);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(&text[matches[0].start..matches[0].end], "It Is Worth Noting");
+        assert_eq!(&text[matches[1].start..matches[1].end], "Furthermore");
+    }
+
+    #[test]
+    fn test_find_ai_phrases_offsets_count_characters_not_bytes() {
+        // "café" puts a 2-byte UTF-8 character before the match, so byte
+        // and char offsets disagree here - this pins down that `start`/`end`
+        // are char offsets, as documented, not byte offsets.
+        let text = "café furthermore";
+        let matches = TextAnalyzer::find_ai_phrases(text);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].start, "café ".chars().count());
+        assert_eq!(matches[0].end, "café ".chars().count() + "furthermore".chars().count());
+    }
+
+    #[test]
+    fn test_score_ai_phrases_counts_distinct_phrases_once() {
+        let repeated = "furthermore furthermore furthermore";
+        let single = "furthermore";
+
+        let repeated_score = TextAnalyzer::score_ai_phrases(&TextAnalyzer::find_ai_phrases(repeated));
+        let single_score = TextAnalyzer::score_ai_phrases(&TextAnalyzer::find_ai_phrases(single));
+
+        assert_eq!(repeated_score, single_score);
+    }
+
+    #[test]
+    fn test_analyze_detailed_reports_all_factors() {
+        let breakdown = TextAnalyzer::analyze_detailed("Some ordinary text with a few words in it.");
+        assert_eq!(breakdown.factors.len(), 6);
+    }
 }
@@ -0,0 +1,415 @@
+/// Authentication Middleware
+///
+/// Exposing `/api/analyze` with no credentials is fine for local
+/// development but unsafe for a public deployment. This module adds an
+/// optional Actix middleware that gates requests behind either:
+///
+/// - a static Bearer token (`AUTH_MODE=bearer`), or
+/// - RFC 7616 Digest authentication (`AUTH_MODE=digest`)
+///
+/// so operators can protect the endpoint without standing up a reverse
+/// proxy. With `AUTH_MODE` unset (or anything else), the middleware is a
+/// no-op and every request passes through untouched.
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, HttpMessage, HttpResponse};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long an issued Digest nonce remains valid before we force a fresh
+/// challenge, regardless of `nc`.
+const NONCE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Hard cap on tracked nonces. Expired entries are purged on every `issue`,
+/// but an attacker hammering the endpoint within a single `NONCE_TTL` window
+/// could still outrun that eviction, so this backstops memory growth.
+const MAX_TRACKED_NONCES: usize = 10_000;
+
+/// Authentication mode selected via environment configuration.
+#[derive(Clone)]
+pub enum AuthMode {
+    /// No authentication required; the middleware is a no-op.
+    None,
+    /// A single static bearer token checked against the `Authorization` header.
+    Bearer { token: String },
+    /// RFC 7616 Digest authentication against one configured user/pass.
+    Digest {
+        realm: String,
+        username: String,
+        password: String,
+    },
+}
+
+impl AuthMode {
+    /// Builds the auth mode from `AUTH_MODE` (`bearer` | `digest`; unset or
+    /// anything else disables auth) plus its associated credentials.
+    pub fn from_env() -> Self {
+        match env::var("AUTH_MODE").unwrap_or_default().to_lowercase().as_str() {
+            "bearer" => AuthMode::Bearer {
+                token: env::var("AUTH_TOKEN").expect("AUTH_TOKEN must be set when AUTH_MODE=bearer"),
+            },
+            "digest" => AuthMode::Digest {
+                realm: env::var("AUTH_REALM").unwrap_or_else(|_| "brbrbr".to_string()),
+                username: env::var("AUTH_USERNAME")
+                    .expect("AUTH_USERNAME must be set when AUTH_MODE=digest"),
+                password: env::var("AUTH_PASSWORD")
+                    .expect("AUTH_PASSWORD must be set when AUTH_MODE=digest"),
+            },
+            _ => AuthMode::None,
+        }
+    }
+}
+
+/// Tracks nonces this server has issued, so we can reject unknown, stale,
+/// or replayed (`nc` not strictly increasing) Digest responses.
+#[derive(Default)]
+struct NonceStore {
+    issued: Mutex<HashMap<String, (Instant, u64)>>,
+}
+
+/// Monotonic counter mixed into every issued nonce so rapid-fire calls
+/// within the same timestamp tick never collide.
+static NONCE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+impl NonceStore {
+    fn issue(&self) -> String {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let counter = NONCE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let nonce = md5_hex(&format!("{}-{}-{}", timestamp, std::process::id(), counter));
+
+        let mut issued = self.issued.lock().unwrap();
+        issued.retain(|_, (issued_at, _)| issued_at.elapsed() <= NONCE_TTL);
+        if issued.len() >= MAX_TRACKED_NONCES {
+            // Still over the cap after purging expired entries (the request
+            // rate is outrunning NONCE_TTL) - drop the oldest rather than
+            // grow further.
+            if let Some(oldest) = issued
+                .iter()
+                .min_by_key(|(_, (issued_at, _))| *issued_at)
+                .map(|(nonce, _)| nonce.clone())
+            {
+                issued.remove(&oldest);
+            }
+        }
+        issued.insert(nonce.clone(), (Instant::now(), 0));
+        nonce
+    }
+
+    /// Validates that `nonce` was issued by us, is not expired, and that
+    /// `nc` is greater than any `nc` already accepted for it (replay
+    /// protection).
+    fn validate_and_advance(&self, nonce: &str, nc: u64) -> bool {
+        let mut issued = self.issued.lock().unwrap();
+        match issued.get_mut(nonce) {
+            Some((issued_at, last_nc)) => {
+                if issued_at.elapsed() > NONCE_TTL {
+                    return false;
+                }
+                if nc <= *last_nc {
+                    return false; // replay
+                }
+                *last_nc = nc;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn md5_hex(input: &str) -> String {
+    format!("{:x}", md5::compute(input.as_bytes()))
+}
+
+/// Parses the comma-separated `key=value` pairs of a `Digest` `Authorization`
+/// header, handling both quoted (`key="value"`) and bare (`key=value`) forms.
+fn parse_digest_params(header_value: &str) -> Option<HashMap<String, String>> {
+    let rest = header_value.strip_prefix("Digest ")?;
+    let mut params = HashMap::new();
+
+    for pair in split_unquoted(rest, ',') {
+        let pair = pair.trim();
+        let (key, value) = pair.split_once('=')?;
+        let value = value.trim().trim_matches('"');
+        params.insert(key.trim().to_string(), value.to_string());
+    }
+
+    Some(params)
+}
+
+/// Splits `s` on `delim`, treating anything between a pair of `"` as opaque
+/// so a quoted value containing `delim` (e.g. a comma inside a Digest `uri`
+/// param) is not split in two.
+fn split_unquoted(s: &str, delim: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c == delim && !in_quotes => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+
+    parts
+}
+
+/// Builds the `WWW-Authenticate: Digest ...` challenge header value.
+fn digest_challenge(realm: &str, nonce: &str) -> String {
+    format!(
+        "Digest realm=\"{}\", nonce=\"{}\", qop=\"auth\", algorithm=MD5",
+        realm, nonce
+    )
+}
+
+/// Verifies a parsed Digest `Authorization` header against the configured
+/// credentials, per RFC 7616: `response == MD5(HA1:nonce:nc:cnonce:qop:HA2)`
+/// where `HA1 = MD5(user:realm:pass)` and `HA2 = MD5(method:uri)`.
+fn verify_digest(
+    params: &HashMap<String, String>,
+    username: &str,
+    password: &str,
+    realm: &str,
+    method: &str,
+    nonces: &NonceStore,
+) -> bool {
+    let get = |key: &str| params.get(key).map(|s| s.as_str());
+
+    let (Some(req_username), Some(req_realm), Some(nonce), Some(uri), Some(nc_str), Some(cnonce), Some(qop), Some(response)) = (
+        get("username"),
+        get("realm"),
+        get("nonce"),
+        get("uri"),
+        get("nc"),
+        get("cnonce"),
+        get("qop"),
+        get("response"),
+    ) else {
+        return false;
+    };
+
+    if req_username != username || req_realm != realm {
+        return false;
+    }
+
+    let Ok(nc) = u64::from_str_radix(nc_str, 16) else {
+        return false;
+    };
+
+    if !nonces.validate_and_advance(nonce, nc) {
+        return false;
+    }
+
+    let ha1 = md5_hex(&format!("{}:{}:{}", username, realm, password));
+    let ha2 = md5_hex(&format!("{}:{}", method, uri));
+    let expected = md5_hex(&format!(
+        "{}:{}:{}:{}:{}:{}",
+        ha1, nonce, nc_str, cnonce, qop, ha2
+    ));
+
+    expected == response
+}
+
+/// Actix middleware factory that applies the configured [`AuthMode`] to
+/// every request it guards.
+#[derive(Clone)]
+pub struct Auth {
+    mode: Arc<AuthMode>,
+    nonces: Arc<NonceStore>,
+}
+
+impl Auth {
+    pub fn new() -> Self {
+        Auth {
+            mode: Arc::new(AuthMode::from_env()),
+            nonces: Arc::new(NonceStore::default()),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Auth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuthMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthMiddleware {
+            service,
+            mode: self.mode.clone(),
+            nonces: self.nonces.clone(),
+        }))
+    }
+}
+
+pub struct AuthMiddleware<S> {
+    service: S,
+    mode: Arc<AuthMode>,
+    nonces: Arc<NonceStore>,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        match self.mode.as_ref() {
+            AuthMode::None => {
+                let fut = self.service.call(req);
+                Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
+            }
+            AuthMode::Bearer { token } => {
+                let authorized = req
+                    .headers()
+                    .get("Authorization")
+                    .and_then(|h| h.to_str().ok())
+                    .map(|v| v == format!("Bearer {}", token))
+                    .unwrap_or(false);
+
+                if authorized {
+                    let fut = self.service.call(req);
+                    Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
+                } else {
+                    let (http_req, _) = req.into_parts();
+                    let response = HttpResponse::Unauthorized()
+                        .insert_header(("WWW-Authenticate", "Bearer"))
+                        .finish()
+                        .map_into_right_body();
+                    Box::pin(async move { Ok(ServiceResponse::new(http_req, response)) })
+                }
+            }
+            AuthMode::Digest {
+                realm,
+                username,
+                password,
+            } => {
+                let method = req.method().as_str().to_string();
+                let parsed = req
+                    .headers()
+                    .get("Authorization")
+                    .and_then(|h| h.to_str().ok())
+                    .and_then(parse_digest_params);
+
+                let authorized = parsed
+                    .as_ref()
+                    .map(|params| {
+                        verify_digest(params, username, password, realm, &method, &self.nonces)
+                    })
+                    .unwrap_or(false);
+
+                if authorized {
+                    let fut = self.service.call(req);
+                    Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
+                } else {
+                    let challenge = digest_challenge(realm, &self.nonces.issue());
+                    let (http_req, _) = req.into_parts();
+                    let response = HttpResponse::Unauthorized()
+                        .insert_header(("WWW-Authenticate", challenge))
+                        .finish()
+                        .map_into_right_body();
+                    Box::pin(async move { Ok(ServiceResponse::new(http_req, response)) })
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_digest_params_handles_quoted_and_bare_values() {
+        let header = r#"Digest username="alice", realm="brbrbr", nonce="abc123", uri="/api/analyze", qop=auth, nc=00000001, cnonce="xyz", response="deadbeef""#;
+        let params = parse_digest_params(header).unwrap();
+
+        assert_eq!(params.get("username").unwrap(), "alice");
+        assert_eq!(params.get("qop").unwrap(), "auth");
+        assert_eq!(params.get("nc").unwrap(), "00000001");
+    }
+
+    #[test]
+    fn test_parse_digest_params_does_not_split_comma_inside_quotes() {
+        let header = r#"Digest username="alice", realm="brbrbr", nonce="abc123", uri="/api/analyze?a=1,b=2", qop=auth, nc=00000001, cnonce="xyz", response="deadbeef""#;
+        let params = parse_digest_params(header).unwrap();
+
+        assert_eq!(params.get("uri").unwrap(), "/api/analyze?a=1,b=2");
+        assert_eq!(params.get("username").unwrap(), "alice");
+    }
+
+    #[test]
+    fn test_issue_evicts_expired_nonces() {
+        let nonces = NonceStore::default();
+        let stale_nonce = nonces.issue();
+
+        // Back-date the entry we just issued so it reads as already expired.
+        nonces
+            .issued
+            .lock()
+            .unwrap()
+            .get_mut(&stale_nonce)
+            .unwrap()
+            .0 = Instant::now() - NONCE_TTL - Duration::from_secs(1);
+
+        nonces.issue();
+
+        assert!(!nonces.issued.lock().unwrap().contains_key(&stale_nonce));
+    }
+
+    #[test]
+    fn test_verify_digest_accepts_valid_response_and_rejects_replay() {
+        let nonces = NonceStore::default();
+        let nonce = nonces.issue();
+
+        let username = "alice";
+        let realm = "brbrbr";
+        let password = "secret";
+        let method = "POST";
+        let uri = "/api/analyze";
+        let nc = "00000001";
+        let cnonce = "clientnonce";
+        let qop = "auth";
+
+        let ha1 = md5_hex(&format!("{}:{}:{}", username, realm, password));
+        let ha2 = md5_hex(&format!("{}:{}", method, uri));
+        let response = md5_hex(&format!("{}:{}:{}:{}:{}:{}", ha1, nonce, nc, cnonce, qop, ha2));
+
+        let mut params = HashMap::new();
+        params.insert("username".to_string(), username.to_string());
+        params.insert("realm".to_string(), realm.to_string());
+        params.insert("nonce".to_string(), nonce.clone());
+        params.insert("uri".to_string(), uri.to_string());
+        params.insert("nc".to_string(), nc.to_string());
+        params.insert("cnonce".to_string(), cnonce.to_string());
+        params.insert("qop".to_string(), qop.to_string());
+        params.insert("response".to_string(), response);
+
+        assert!(verify_digest(&params, username, password, realm, method, &nonces));
+        // Replaying the same nc must fail.
+        assert!(!verify_digest(&params, username, password, realm, method, &nonces));
+    }
+}
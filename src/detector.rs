@@ -0,0 +1,207 @@
+/// Detector Backend Abstraction
+///
+/// Defines a common `Detector` trait implemented by each scoring backend
+/// (the built-in heuristic analyzer, the Hugging Face API, and any future
+/// provider), plus an `EnsembleDetector` that combines several backends
+/// into a single score. This keeps the web layer ignorant of which
+/// backends are configured.
+use async_trait::async_trait;
+
+use crate::analyzer::TextAnalyzer;
+use crate::huggingface::{self, HfError};
+
+/// Error returned by any `Detector` implementation.
+#[derive(Debug)]
+pub enum DetectError {
+    HuggingFace(HfError),
+    Config(String),
+}
+
+impl std::fmt::Display for DetectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DetectError::HuggingFace(err) => write!(f, "Hugging Face backend error: {}", err),
+            DetectError::Config(msg) => write!(f, "Config Error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for DetectError {}
+
+impl From<HfError> for DetectError {
+    fn from(err: HfError) -> Self {
+        DetectError::HuggingFace(err)
+    }
+}
+
+/// A backend's score plus whatever backend-specific detail it can offer
+/// beyond the bare number - currently only the Hugging Face backend's
+/// per-chunk breakdown.
+pub struct DetectionResult {
+    pub ai_percentage: f32,
+    pub chunks: Option<Vec<huggingface::ChunkScore>>,
+}
+
+/// Common interface implemented by every AI-detection backend.
+#[async_trait]
+pub trait Detector: Send + Sync {
+    /// Score `text` as an AI probability in the 0-100 range.
+    async fn score(&self, text: &str) -> Result<f32, DetectError>;
+
+    /// Score `text`, additionally surfacing any backend-specific detail
+    /// (e.g. a per-chunk breakdown). Defaults to wrapping `score` with no
+    /// extra detail, for backends that don't have any.
+    async fn score_detailed(&self, text: &str) -> Result<DetectionResult, DetectError> {
+        Ok(DetectionResult {
+            ai_percentage: self.score(text).await?,
+            chunks: None,
+        })
+    }
+
+    /// A short, human-readable name for logging and API responses.
+    fn name(&self) -> &'static str;
+}
+
+/// Backend that scores text using the local heuristic `TextAnalyzer`.
+/// Always available; never fails.
+pub struct HeuristicDetector;
+
+#[async_trait]
+impl Detector for HeuristicDetector {
+    async fn score(&self, text: &str) -> Result<f32, DetectError> {
+        Ok(TextAnalyzer::analyze(text))
+    }
+
+    fn name(&self) -> &'static str {
+        "heuristic"
+    }
+}
+
+/// Backend that scores text using the Hugging Face inference API.
+/// Requires `HF_API_TOKEN` to be set in the environment.
+pub struct HuggingFaceDetector;
+
+#[async_trait]
+impl Detector for HuggingFaceDetector {
+    async fn score(&self, text: &str) -> Result<f32, DetectError> {
+        Ok(self.score_detailed(text).await?.ai_percentage)
+    }
+
+    async fn score_detailed(&self, text: &str) -> Result<DetectionResult, DetectError> {
+        let analysis =
+            huggingface::analyze_with_huggingface_detailed(text, huggingface::AggregationPolicy::from_env())
+                .await?;
+        Ok(DetectionResult {
+            ai_percentage: analysis.ai_percentage,
+            chunks: Some(analysis.chunks),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "huggingface"
+    }
+}
+
+/// How an `EnsembleDetector` combines scores from its backends.
+pub enum EnsembleStrategy {
+    /// Average backend scores, weighted in the same order as `backends`.
+    /// Backends that fail are skipped and excluded from the weight total.
+    WeightedAverage(Vec<f32>),
+    /// Use the first backend that succeeds, in order.
+    FirstAvailable,
+}
+
+/// Runs a configured list of `Detector` backends and combines their scores.
+pub struct EnsembleDetector {
+    backends: Vec<Box<dyn Detector>>,
+    strategy: EnsembleStrategy,
+}
+
+impl EnsembleDetector {
+    pub fn new(backends: Vec<Box<dyn Detector>>, strategy: EnsembleStrategy) -> Self {
+        EnsembleDetector { backends, strategy }
+    }
+
+    /// Builds the ensemble this deployment should use: the Hugging Face
+    /// backend if `HF_API_TOKEN` is configured (weighted higher than the
+    /// heuristic), falling back to the heuristic alone otherwise.
+    pub fn from_env() -> Self {
+        if std::env::var("HF_API_TOKEN").is_ok() {
+            EnsembleDetector::new(
+                vec![Box::new(HuggingFaceDetector), Box::new(HeuristicDetector)],
+                EnsembleStrategy::WeightedAverage(vec![0.8, 0.2]),
+            )
+        } else {
+            EnsembleDetector::new(
+                vec![Box::new(HeuristicDetector)],
+                EnsembleStrategy::FirstAvailable,
+            )
+        }
+    }
+}
+
+#[async_trait]
+impl Detector for EnsembleDetector {
+    async fn score(&self, text: &str) -> Result<f32, DetectError> {
+        Ok(self.score_detailed(text).await?.ai_percentage)
+    }
+
+    /// Combines backend scores per `self.strategy`, same as `score`, and
+    /// additionally carries through the first chunk breakdown produced by
+    /// any backend (in practice, the Hugging Face one) so callers can show
+    /// it without a second, duplicate request to that backend.
+    async fn score_detailed(&self, text: &str) -> Result<DetectionResult, DetectError> {
+        match &self.strategy {
+            EnsembleStrategy::FirstAvailable => {
+                for backend in &self.backends {
+                    if let Ok(result) = backend.score_detailed(text).await {
+                        return Ok(result);
+                    }
+                }
+                Err(DetectError::Config("no detector backend available".to_string()))
+            }
+            EnsembleStrategy::WeightedAverage(weights) => {
+                let mut weighted_sum = 0.0;
+                let mut total_weight = 0.0;
+                let mut chunks = None;
+
+                for (backend, weight) in self.backends.iter().zip(weights.iter()) {
+                    if let Ok(result) = backend.score_detailed(text).await {
+                        weighted_sum += result.ai_percentage * weight;
+                        total_weight += weight;
+                        if chunks.is_none() {
+                            chunks = result.chunks;
+                        }
+                    }
+                }
+
+                if total_weight == 0.0 {
+                    return Err(DetectError::Config(
+                        "no detector backend available".to_string(),
+                    ));
+                }
+
+                Ok(DetectionResult {
+                    ai_percentage: weighted_sum / total_weight,
+                    chunks,
+                })
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "ensemble"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_heuristic_detector_never_fails() {
+        let detector = HeuristicDetector;
+        let score = detector.score("Some test input.").await.unwrap();
+        assert!((0.0..=100.0).contains(&score));
+    }
+}
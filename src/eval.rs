@@ -0,0 +1,156 @@
+/// Detector Evaluation Module
+///
+/// Implements the `--eval <dataset>` mode: loads a labeled dataset of
+/// `(text, is_ai)` examples, runs each detector backend over it, and
+/// reports precision/recall/F1/accuracy plus a confusion matrix and a
+/// threshold sweep. This lets us compare backends quantitatively and
+/// tune `TextAnalyzer::get_verdict`'s cutoffs empirically instead of
+/// guessing.
+use serde::Deserialize;
+use std::fs;
+
+use crate::detector::{Detector, HeuristicDetector, HuggingFaceDetector};
+
+/// A single labeled example: `is_ai` is the ground-truth label.
+#[derive(Deserialize)]
+struct EvalExample {
+    text: String,
+    is_ai: bool,
+}
+
+/// Precision/recall/F1/accuracy for one backend at one threshold.
+struct Metrics {
+    threshold: f32,
+    true_positives: u32,
+    false_positives: u32,
+    true_negatives: u32,
+    false_negatives: u32,
+}
+
+impl Metrics {
+    fn precision(&self) -> f32 {
+        let denom = self.true_positives + self.false_positives;
+        if denom == 0 {
+            0.0
+        } else {
+            self.true_positives as f32 / denom as f32
+        }
+    }
+
+    fn recall(&self) -> f32 {
+        let denom = self.true_positives + self.false_negatives;
+        if denom == 0 {
+            0.0
+        } else {
+            self.true_positives as f32 / denom as f32
+        }
+    }
+
+    fn f1(&self) -> f32 {
+        let p = self.precision();
+        let r = self.recall();
+        if p + r == 0.0 {
+            0.0
+        } else {
+            2.0 * p * r / (p + r)
+        }
+    }
+
+    fn accuracy(&self) -> f32 {
+        let total =
+            self.true_positives + self.false_positives + self.true_negatives + self.false_negatives;
+        if total == 0 {
+            0.0
+        } else {
+            (self.true_positives + self.true_negatives) as f32 / total as f32
+        }
+    }
+}
+
+fn load_examples(dataset_path: &str) -> Vec<EvalExample> {
+    let contents = fs::read_to_string(dataset_path)
+        .unwrap_or_else(|e| panic!("failed to read dataset {}: {}", dataset_path, e));
+
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).expect("invalid eval example JSON line"))
+        .collect()
+}
+
+fn metrics_at_threshold(examples: &[EvalExample], scores: &[f32], threshold: f32) -> Metrics {
+    let mut m = Metrics {
+        threshold,
+        true_positives: 0,
+        false_positives: 0,
+        true_negatives: 0,
+        false_negatives: 0,
+    };
+
+    for (example, &score) in examples.iter().zip(scores.iter()) {
+        let predicted_ai = score >= threshold;
+        match (predicted_ai, example.is_ai) {
+            (true, true) => m.true_positives += 1,
+            (true, false) => m.false_positives += 1,
+            (false, true) => m.false_negatives += 1,
+            (false, false) => m.true_negatives += 1,
+        }
+    }
+
+    m
+}
+
+async fn score_all(detector: &dyn Detector, examples: &[EvalExample]) -> Vec<f32> {
+    let mut scores = Vec::with_capacity(examples.len());
+    for example in examples {
+        let score = detector.score(&example.text).await.unwrap_or(50.0);
+        scores.push(score);
+    }
+    scores
+}
+
+/// Runs the evaluation against `dataset_path` and prints a report to stdout.
+pub async fn run_eval(dataset_path: &str) {
+    let examples = load_examples(dataset_path);
+    println!("Loaded {} labeled examples from {}", examples.len(), dataset_path);
+
+    let mut backends: Vec<(&str, Box<dyn Detector>)> = vec![("heuristic", Box::new(HeuristicDetector))];
+    if std::env::var("HF_API_TOKEN").is_ok() {
+        backends.push(("huggingface", Box::new(HuggingFaceDetector)));
+    }
+
+    for (name, detector) in &backends {
+        let scores = score_all(detector.as_ref(), &examples).await;
+
+        // Report at the analyzer's current "AI Generated" cutoff (60.0);
+        // the 40.0 cutoff appears only in the threshold sweep below.
+        let default = metrics_at_threshold(&examples, &scores, 60.0);
+        println!(
+            "\n== {} backend @ threshold 60.0 ==\nprecision={:.3} recall={:.3} f1={:.3} accuracy={:.3}",
+            name,
+            default.precision(),
+            default.recall(),
+            default.f1(),
+            default.accuracy()
+        );
+        println!(
+            "confusion matrix: TP={} FP={} TN={} FN={}",
+            default.true_positives, default.false_positives, default.true_negatives, default.false_negatives
+        );
+
+        // Sweep thresholds to help retune `get_verdict`'s cutoffs.
+        println!("\nthreshold  precision  recall  f1       accuracy");
+        for step in 1..10 {
+            let threshold = step as f32 * 10.0;
+            let m = metrics_at_threshold(&examples, &scores, threshold);
+            println!(
+                "{:9.1}  {:9.3}  {:6.3}  {:7.3}  {:8.3}",
+                m.threshold,
+                m.precision(),
+                m.recall(),
+                m.f1(),
+                m.accuracy()
+            );
+        }
+    }
+}
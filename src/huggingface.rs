@@ -2,9 +2,26 @@
 ///
 /// This module provides integration with Hugging Face's AI text detection models.
 /// Uses the roberta-base-openai-detector model for high-accuracy AI detection.
-
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Hugging Face model endpoint.
+/// Using Hello-SimpleAI/chatgpt-detector-roberta for AI detection.
+const MODEL_URL: &str = "https://api-inference.huggingface.co/models/Hello-SimpleAI/chatgpt-detector-roberta";
+
+/// Most roberta-style detectors truncate around 512 tokens. We budget
+/// ~4 characters per token and stay comfortably under that so a chunk
+/// is never silently truncated by the model itself.
+const MAX_CHUNK_CHARS: usize = 1600;
+
+/// Overlap carried from the end of one chunk into the start of the next,
+/// so a sentence spanning a chunk boundary still gets full context.
+const CHUNK_OVERLAP_CHARS: usize = 200;
+
+/// Maximum number of chunk requests in flight at once.
+const MAX_CONCURRENT_REQUESTS: usize = 4;
 
 /// Request payload for Hugging Face inference API
 #[derive(Serialize)]
@@ -42,6 +59,48 @@ impl std::fmt::Display for HfError {
 
 impl std::error::Error for HfError {}
 
+/// How per-chunk AI probabilities are combined into a single score.
+#[derive(Clone, Copy, Debug)]
+pub enum AggregationPolicy {
+    /// Plain average across chunks.
+    Mean,
+    /// The single most AI-like chunk decides the whole document's score.
+    Max,
+    /// Average weighted by each chunk's character length.
+    LengthWeightedMean,
+}
+
+impl AggregationPolicy {
+    /// Reads `HF_AGGREGATION_POLICY` (`mean` | `max` | `length_weighted`;
+    /// unset or anything else defaults to `LengthWeightedMean`).
+    pub fn from_env() -> Self {
+        match env::var("HF_AGGREGATION_POLICY")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "mean" => AggregationPolicy::Mean,
+            "max" => AggregationPolicy::Max,
+            _ => AggregationPolicy::LengthWeightedMean,
+        }
+    }
+}
+
+/// AI probability (0-100) for one chunk of the original text.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkScore {
+    pub text: String,
+    pub ai_percentage: f32,
+}
+
+/// Full result of analyzing a (possibly long) document, including the
+/// per-chunk breakdown that produced the aggregate score.
+#[derive(Debug, Clone)]
+pub struct HfAnalysis {
+    pub ai_percentage: f32,
+    pub chunks: Vec<ChunkScore>,
+}
+
 /// Main function to analyze text using Hugging Face API
 ///
 /// # Arguments
@@ -50,25 +109,198 @@ impl std::error::Error for HfError {}
 /// # Returns
 /// * `Result<f32, HfError>` - AI probability score (0-100) or error
 pub async fn analyze_with_huggingface(text: &str) -> Result<f32, HfError> {
-    // Get API token from environment variable
+    let analysis = analyze_with_huggingface_detailed(text, AggregationPolicy::from_env()).await?;
+    Ok(analysis.ai_percentage)
+}
+
+/// Analyzes (possibly long) text by splitting it into overlapping,
+/// model-sized chunks, scoring each chunk concurrently (bounded by
+/// `MAX_CONCURRENT_REQUESTS`), and aggregating the results according to
+/// `policy`. Returns the per-chunk breakdown alongside the aggregate score
+/// so callers can show which parts of a document looked AI-generated.
+pub async fn analyze_with_huggingface_detailed(
+    text: &str,
+    policy: AggregationPolicy,
+) -> Result<HfAnalysis, HfError> {
     let api_token = env::var("HF_API_TOKEN")
         .map_err(|_| HfError::ConfigError("HF_API_TOKEN not set in environment".to_string()))?;
 
-    // Hugging Face model endpoint
-    // Using Hello-SimpleAI/chatgpt-detector-roberta for AI detection
-    let model_url = "https://api-inference.huggingface.co/models/Hello-SimpleAI/chatgpt-detector-roberta";
-
-    // Create HTTP client
+    let chunks = split_into_chunks(text);
     let client = reqwest::Client::new();
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS));
+
+    let mut handles = Vec::with_capacity(chunks.len());
+    for chunk in &chunks {
+        let client = client.clone();
+        let api_token = api_token.clone();
+        let chunk = chunk.clone();
+        let semaphore = semaphore.clone();
+
+        handles.push(tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await.expect("semaphore closed");
+            score_chunk(&client, &api_token, &chunk).await
+        }));
+    }
 
-    // Prepare request payload
+    let mut chunk_scores = Vec::with_capacity(chunks.len());
+    for (chunk, handle) in chunks.into_iter().zip(handles) {
+        let ai_score = handle
+            .await
+            .map_err(|e| HfError::NetworkError(e.to_string()))??;
+        chunk_scores.push(ChunkScore {
+            text: chunk,
+            ai_percentage: ai_score * 100.0,
+        });
+    }
+
+    let ai_percentage = aggregate_scores(&chunk_scores, policy);
+
+    Ok(HfAnalysis {
+        ai_percentage,
+        chunks: chunk_scores,
+    })
+}
+
+/// Splits `text` into sentence-aware, overlapping windows that each fit
+/// under the model's token limit, so long documents aren't judged on only
+/// their opening chunk.
+fn split_into_chunks(text: &str) -> Vec<String> {
+    let sentences: Vec<&str> = text
+        .split_inclusive(&['.', '!', '?'][..])
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if sentences.is_empty() {
+        return vec![text.to_string()];
+    }
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for sentence in &sentences {
+        // A single sentence (or unpunctuated span) longer than the chunk
+        // budget would never be split by the logic below, so carve it up
+        // on its own first.
+        if sentence.len() > MAX_CHUNK_CHARS {
+            if !current.is_empty() {
+                chunks.push(current.clone());
+                current.clear();
+            }
+            let mut pieces = split_long_span(sentence);
+            current = pieces.pop().unwrap_or_default();
+            chunks.extend(pieces);
+            continue;
+        }
+
+        if !current.is_empty() && current.len() + sentence.len() + 1 > MAX_CHUNK_CHARS {
+            chunks.push(current.clone());
+            // Carry the tail of the current chunk into the next one so a
+            // sentence near the boundary keeps surrounding context. Snap up
+            // to the next char boundary first since `len() - max_overlap` is
+            // a raw byte index and may land mid multi-byte character. Cap the
+            // overlap itself to whatever room the next sentence leaves in
+            // the budget, so prepending it can never push the new chunk
+            // over MAX_CHUNK_CHARS.
+            let max_overlap = CHUNK_OVERLAP_CHARS.min(MAX_CHUNK_CHARS.saturating_sub(sentence.len() + 1));
+            let mut overlap_start = current.len().saturating_sub(max_overlap);
+            while !current.is_char_boundary(overlap_start) {
+                overlap_start += 1;
+            }
+            current = current[overlap_start..].to_string();
+        }
+
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(sentence);
+    }
+
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+
+    if chunks.is_empty() {
+        chunks.push(text.to_string());
+    }
+
+    chunks
+}
+
+/// Snaps `index` down to the nearest UTF-8 char boundary in `s`, so a byte
+/// offset derived from arithmetic (not from a known char position) can
+/// always be used to slice safely.
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut idx = index.min(s.len());
+    while idx > 0 && !s.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+/// Splits a span longer than `MAX_CHUNK_CHARS` into char-boundary-safe
+/// pieces of at most that length, so no single chunk ever silently exceeds
+/// the model's token budget.
+fn split_long_span(span: &str) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest = span;
+
+    while rest.len() > MAX_CHUNK_CHARS {
+        let mut cut = floor_char_boundary(rest, MAX_CHUNK_CHARS);
+        if cut == 0 {
+            // A single char wider than the limit (impossible for
+            // MAX_CHUNK_CHARS = 1600, as a UTF-8 char is at most 4 bytes) -
+            // take it whole rather than loop forever.
+            cut = rest.chars().next().map(|c| c.len_utf8()).unwrap_or(rest.len());
+        }
+        pieces.push(rest[..cut].to_string());
+        rest = &rest[cut..];
+    }
+
+    if !rest.is_empty() {
+        pieces.push(rest.to_string());
+    }
+
+    pieces
+}
+
+/// Aggregates per-chunk AI probabilities into a single document score.
+fn aggregate_scores(chunks: &[ChunkScore], policy: AggregationPolicy) -> f32 {
+    if chunks.is_empty() {
+        return 50.0;
+    }
+
+    match policy {
+        AggregationPolicy::Mean => {
+            chunks.iter().map(|c| c.ai_percentage).sum::<f32>() / chunks.len() as f32
+        }
+        AggregationPolicy::Max => chunks
+            .iter()
+            .map(|c| c.ai_percentage)
+            .fold(f32::MIN, f32::max),
+        AggregationPolicy::LengthWeightedMean => {
+            let total_len: usize = chunks.iter().map(|c| c.text.len()).sum();
+            if total_len == 0 {
+                return 50.0;
+            }
+            chunks
+                .iter()
+                .map(|c| c.ai_percentage * c.text.len() as f32)
+                .sum::<f32>()
+                / total_len as f32
+        }
+    }
+}
+
+/// Scores a single chunk of text against the Hugging Face inference API.
+/// Returns the raw AI probability in the 0.0-1.0 range.
+async fn score_chunk(client: &reqwest::Client, api_token: &str, text: &str) -> Result<f32, HfError> {
     let request_body = HfRequest {
         inputs: text.to_string(),
     };
 
-    // Make API request
     let response = client
-        .post(model_url)
+        .post(MODEL_URL)
         .header("Authorization", format!("Bearer {}", api_token))
         .header("Content-Type", "application/json")
         .json(&request_body)
@@ -76,24 +308,17 @@ pub async fn analyze_with_huggingface(text: &str) -> Result<f32, HfError> {
         .await
         .map_err(|e| HfError::NetworkError(e.to_string()))?;
 
-    // Check response status
     if !response.status().is_success() {
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
         return Err(HfError::ApiError(format!("API returned error: {}", error_text)));
     }
 
-    // Parse response
     let hf_results: Vec<Vec<HfResponse>> = response
         .json()
         .await
         .map_err(|e| HfError::ParseError(e.to_string()))?;
 
-    // Extract AI probability from results
-    // The model returns [{"label": "Human", "score": 0.1}, {"label": "ChatGPT", "score": 0.9}]
-    // We want the "ChatGPT" (AI-generated) score
-    let ai_score = extract_ai_score(&hf_results)?;
-
-    Ok(ai_score * 100.0) // Convert to percentage
+    extract_ai_score(&hf_results)
 }
 
 /// Extract AI probability score from Hugging Face response
@@ -153,4 +378,63 @@ mod tests {
         let result = extract_ai_score(&mock_response).unwrap();
         assert_eq!(result, 0.15);
     }
+
+    #[test]
+    fn test_split_into_chunks_short_text_is_single_chunk() {
+        let chunks = split_into_chunks("Short text that fits in one chunk.");
+        assert_eq!(chunks.len(), 1);
+    }
+
+    #[test]
+    fn test_split_into_chunks_does_not_panic_on_multibyte_overlap_boundary() {
+        // Swedish å/ä/ö are 2-byte UTF-8 sequences. Each sentence here is
+        // 1001 bytes, so two never fit in one chunk and the overlap cut
+        // (`len() - CHUNK_OVERLAP_CHARS`, an odd byte index) lands
+        // mid-character unless it's snapped to a char boundary first.
+        let sentence = format!("{}.", "ö".repeat(500));
+        let text = sentence.repeat(3);
+        let chunks = split_into_chunks(&text);
+        assert!(chunks.len() > 1);
+    }
+
+    #[test]
+    fn test_split_into_chunks_never_exceeds_budget_even_with_overlap() {
+        // A run of near-max-length sentences forces the overlap carried
+        // into each new chunk to be trimmed, or prepending the full
+        // CHUNK_OVERLAP_CHARS to the next sentence would push the chunk
+        // past MAX_CHUNK_CHARS.
+        let sentence = format!("{}.", "word ".repeat((MAX_CHUNK_CHARS - 100) / 5));
+        let text = sentence.repeat(3);
+
+        let chunks = split_into_chunks(&text);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= MAX_CHUNK_CHARS));
+    }
+
+    #[test]
+    fn test_split_long_span_without_punctuation_is_still_chunked() {
+        let long_span = "a".repeat(MAX_CHUNK_CHARS * 3);
+        let pieces = split_long_span(&long_span);
+
+        assert!(pieces.len() >= 3);
+        assert!(pieces.iter().all(|p| p.len() <= MAX_CHUNK_CHARS));
+    }
+
+    #[test]
+    fn test_aggregate_scores_length_weighted_mean() {
+        let chunks = vec![
+            ChunkScore {
+                text: "a".repeat(100),
+                ai_percentage: 80.0,
+            },
+            ChunkScore {
+                text: "b".repeat(300),
+                ai_percentage: 20.0,
+            },
+        ];
+
+        let score = aggregate_scores(&chunks, AggregationPolicy::LengthWeightedMean);
+        assert!((score - 35.0).abs() < 0.01);
+    }
 }
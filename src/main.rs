@@ -1,4 +1,8 @@
 mod analyzer;
+mod auth;
+mod detector;
+mod eval;
+mod huggingface;
 
 use actix_web::{web, App, HttpResponse, HttpServer, Responder};
 use actix_cors::Cors;
@@ -6,17 +10,52 @@ use actix_files::{Files, NamedFile};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use analyzer::TextAnalyzer;
+use auth::Auth;
+use detector::{Detector, EnsembleDetector};
 
 #[derive(Deserialize)]
 struct AnalyzeRequest {
     text: String,
 }
 
+/// One scoring factor's contribution to the overall AI percentage.
+#[derive(Serialize)]
+struct FactorBreakdown {
+    name: &'static str,
+    score: f32,
+    weight: f32,
+}
+
+/// An AI-phrase match found in the text, with offsets so a frontend can
+/// highlight it.
+#[derive(Serialize)]
+struct PhraseMatch {
+    phrase: String,
+    start: usize,
+    end: usize,
+}
+
+/// One chunk of the original text and its own AI percentage, from the
+/// Hugging Face backend. `None` in the response when that backend isn't
+/// configured, since the heuristic backend has no per-chunk notion.
+#[derive(Serialize)]
+struct ChunkBreakdown {
+    text: String,
+    ai_percentage: f32,
+}
+
 #[derive(Serialize)]
 struct AnalyzeResponse {
     human_percentage: f32,
     ai_percentage: f32,
     verdict: String,
+    /// Per-factor breakdown of the heuristic analyzer's contribution,
+    /// so a verdict isn't just an opaque number.
+    factors: Vec<FactorBreakdown>,
+    /// AI-common phrases actually found in the text, with offsets.
+    matched_phrases: Vec<PhraseMatch>,
+    /// Per-chunk Hugging Face breakdown, when that backend is configured.
+    chunks: Option<Vec<ChunkBreakdown>>,
 }
 
 async fn health_check() -> impl Responder {
@@ -29,15 +68,61 @@ async fn health_check() -> impl Responder {
 async fn analyze_text(req: web::Json<AnalyzeRequest>) -> impl Responder {
     let text = &req.text;
 
-    // Perform AI detection analysis
-    let ai_percentage = TextAnalyzer::analyze(text);
+    // Select backends from config/env (Hugging Face if configured, else the
+    // heuristic analyzer alone) rather than calling `TextAnalyzer` directly.
+    let detector = EnsembleDetector::from_env();
+
+    let detection = match detector.score_detailed(text).await {
+        Ok(detection) => detection,
+        Err(err) => {
+            return HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "error": err.to_string()
+            }));
+        }
+    };
+    let ai_percentage = detection.ai_percentage;
+    let chunks = detection.chunks.map(|chunks| {
+        chunks
+            .into_iter()
+            .map(|c| ChunkBreakdown {
+                text: c.text,
+                ai_percentage: c.ai_percentage,
+            })
+            .collect()
+    });
     let human_percentage = 100.0 - ai_percentage;
     let verdict = TextAnalyzer::get_verdict(ai_percentage);
 
+    // The per-factor explanation comes from the heuristic analyzer, since
+    // it's the only backend with an interpretable breakdown; the ensemble
+    // score above may also incorporate the Hugging Face model.
+    let breakdown = TextAnalyzer::analyze_detailed(text);
+    let factors = breakdown
+        .factors
+        .into_iter()
+        .map(|f| FactorBreakdown {
+            name: f.name,
+            score: f.score,
+            weight: f.weight,
+        })
+        .collect();
+    let matched_phrases = breakdown
+        .matched_phrases
+        .into_iter()
+        .map(|m| PhraseMatch {
+            phrase: m.phrase,
+            start: m.start,
+            end: m.end,
+        })
+        .collect();
+
     let response = AnalyzeResponse {
         human_percentage,
         ai_percentage,
         verdict,
+        factors,
+        matched_phrases,
+        chunks,
     };
 
     HttpResponse::Ok().json(response)
@@ -50,9 +135,24 @@ async fn index() -> actix_web::Result<NamedFile> {
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    // `--eval <dataset>` runs the benchmarking mode against a labeled
+    // dataset instead of starting the web server.
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pos) = args.iter().position(|a| a == "--eval") {
+        let dataset_path = args.get(pos + 1).expect("--eval requires a dataset path");
+        eval::run_eval(dataset_path).await;
+        return Ok(());
+    }
+
     println!("🚀 Starting brbrbr server on http://localhost:8080");
 
-    HttpServer::new(|| {
+    // Built once and cloned into each worker below, so every worker shares
+    // the same nonce store - constructing it inside the factory closure
+    // would give each worker its own, and a Digest challenge issued by one
+    // worker would never validate on another.
+    let auth = Auth::new();
+
+    HttpServer::new(move || {
         let cors = Cors::default()
             .allow_any_origin()
             .allow_any_method()
@@ -61,7 +161,11 @@ async fn main() -> std::io::Result<()> {
         App::new()
             .wrap(cors)
             .route("/health", web::get().to(health_check))
-            .route("/api/analyze", web::post().to(analyze_text))
+            .service(
+                web::scope("/api")
+                    .wrap(auth.clone())
+                    .route("/analyze", web::post().to(analyze_text)),
+            )
             .service(Files::new("/assets", "./static/assets"))
             .route("/", web::get().to(index))
             .default_service(web::get().to(index))